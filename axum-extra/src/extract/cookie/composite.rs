@@ -0,0 +1,324 @@
+use super::{cookies_from_request, set_cookies, Cookie, Key};
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    response::{IntoResponse, IntoResponseParts, Response, ResponseParts},
+};
+use http::{request::Parts, HeaderMap};
+use std::{collections::HashMap, convert::Infallible, fmt, marker::PhantomData};
+
+/// Which cryptographic protection a cookie in a [`ProtectedCookieJar`] was stored under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protection {
+    /// The cookie is authenticated (tamper-evident) but its value is readable in plaintext by
+    /// the client, via [`ProtectedCookieJar::signed`].
+    Signed,
+    /// The cookie is encrypted and authenticated, via [`ProtectedCookieJar::private`].
+    Private,
+}
+
+/// Extractor that manages a single cookie jar in which each cookie is individually either
+/// signed (authenticated, plaintext) or private (authenticated, encrypted), both backed by the
+/// same [`Key`].
+///
+/// This is useful when some cookies only need integrity (e.g. a CSRF token the client is meant
+/// to read) while others need confidentiality (e.g. session data), without maintaining two
+/// separate jars and two separate `FromRef` lookups.
+///
+/// Like [`SignedCookieJar`](super::SignedCookieJar) and [`PrivateCookieJar`](super::PrivateCookieJar),
+/// this value _must_ be returned from the handler as part of the response for changes to be
+/// propagated.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{extract::FromRef, response::IntoResponse, routing::get, Router};
+/// use axum_extra::extract::cookie::{Cookie, Key, ProtectedCookieJar};
+///
+/// async fn handler(jar: ProtectedCookieJar) -> (ProtectedCookieJar, &'static str) {
+///     let jar = jar
+///         .signed()
+///         .add(Cookie::new("csrf_token", "readable-but-tamper-evident"))
+///         .into_jar()
+///         .private()
+///         .add(Cookie::new("session", "encrypted-and-tamper-evident"))
+///         .into_jar();
+///     (jar, "ok")
+/// }
+///
+/// #[derive(Clone)]
+/// struct AppState {
+///     key: Key,
+/// }
+///
+/// impl FromRef<AppState> for Key {
+///     fn from_ref(state: &AppState) -> Self {
+///         state.key.clone()
+///     }
+/// }
+///
+/// let state = AppState { key: Key::generate() };
+/// let app = Router::new().route("/", get(handler)).with_state(state);
+/// # let _: axum::Router = app;
+/// ```
+pub struct ProtectedCookieJar<K = Key> {
+    jar: cookie::CookieJar,
+    key: Key,
+    // Which protection each cookie was stored under, keyed by cookie name, so reads know whether
+    // to verify or decrypt.
+    protection: HashMap<String, Protection>,
+    _marker: PhantomData<K>,
+}
+
+impl<K> fmt::Debug for ProtectedCookieJar<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProtectedCookieJar")
+            .field("jar", &self.jar)
+            .field("key", &"REDACTED")
+            .field("protection", &self.protection)
+            .finish()
+    }
+}
+
+impl<S, K> FromRequestParts<S> for ProtectedCookieJar<K>
+where
+    S: Send + Sync,
+    K: FromRef<S> + Into<Key>,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let key = K::from_ref(state).into();
+        let ProtectedCookieJar {
+            jar,
+            key,
+            protection,
+            _marker: _,
+        } = ProtectedCookieJar::from_headers(&parts.headers, key);
+        Ok(ProtectedCookieJar {
+            jar,
+            key,
+            protection,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl ProtectedCookieJar {
+    /// Create a new `ProtectedCookieJar` from a map of request headers.
+    ///
+    /// Each cookie in `headers` is accepted if it validates as either a private or a signed
+    /// cookie under `key`; private is tried first. Cookies that validate under neither are
+    /// dropped, just like [`PrivateCookieJar::from_headers`](super::PrivateCookieJar::from_headers).
+    ///
+    /// This is intended to be used in middleware and other places where it might be difficult to
+    /// run extractors. Normally you should create `ProtectedCookieJar`s through [`FromRequestParts`].
+    ///
+    /// [`FromRequestParts`]: axum::extract::FromRequestParts
+    pub fn from_headers(headers: &HeaderMap, key: Key) -> Self {
+        let mut jar = cookie::CookieJar::new();
+        let mut protection = HashMap::new();
+
+        for cookie in cookies_from_request(headers) {
+            let name = cookie.name().to_owned();
+            if let Some(plaintext) = jar.private(&key).decrypt(cookie.clone()) {
+                jar.private_mut(&key).add_original(plaintext);
+                protection.insert(name, Protection::Private);
+            } else if let Some(plaintext) = jar.signed(&key).verify(cookie) {
+                jar.signed_mut(&key).add_original(plaintext);
+                protection.insert(name, Protection::Signed);
+            }
+        }
+
+        Self {
+            jar,
+            key,
+            protection,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Create a new empty `ProtectedCookieJar`.
+    ///
+    /// This is intended to be used in middleware and other places where it might be difficult to
+    /// run extractors. Normally you should create `ProtectedCookieJar`s through [`FromRequestParts`].
+    ///
+    /// [`FromRequestParts`]: axum::extract::FromRequestParts
+    pub fn new(key: Key) -> Self {
+        Self {
+            jar: Default::default(),
+            key,
+            protection: HashMap::new(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K> ProtectedCookieJar<K> {
+    /// Get a cookie from the jar, reading it back with whichever protection it was stored under.
+    ///
+    /// Returns `None` if the cookie doesn't exist or fails to verify/decrypt.
+    pub fn get(&self, name: &str) -> Option<Cookie<'static>> {
+        match self.protection.get(name)? {
+            Protection::Signed => self.jar.signed(&self.key).get(name),
+            Protection::Private => self.jar.private(&self.key).get(name),
+        }
+    }
+
+    /// Remove a cookie from the jar, regardless of which protection it was stored under.
+    #[must_use]
+    pub fn remove<C: Into<Cookie<'static>>>(mut self, cookie: C) -> Self {
+        let cookie = cookie.into();
+        self.protection.remove(cookie.name());
+        self.jar.remove(cookie);
+        self
+    }
+
+    /// Get an iterator over all cookies in the jar, together with the protection each one is
+    /// stored under.
+    ///
+    /// Only cookies with valid authenticity (and, for private cookies, integrity) are yielded.
+    pub fn iter(&self) -> impl Iterator<Item = (Cookie<'static>, Protection)> + '_ {
+        self.protection
+            .iter()
+            .filter_map(|(name, protection)| Some((self.get(name)?, *protection)))
+    }
+
+    /// Get a view onto this jar for reading and writing signed (tamper-evident, readable)
+    /// cookies.
+    #[must_use]
+    pub fn signed(self) -> SignedView<K> {
+        SignedView { jar: self }
+    }
+
+    /// Get a view onto this jar for reading and writing private (encrypted, tamper-evident)
+    /// cookies.
+    #[must_use]
+    pub fn private(self) -> PrivateView<K> {
+        PrivateView { jar: self }
+    }
+}
+
+impl<K> IntoResponseParts for ProtectedCookieJar<K> {
+    type Error = Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        set_cookies(self.jar, res.headers_mut());
+        Ok(res)
+    }
+}
+
+impl<K> IntoResponse for ProtectedCookieJar<K> {
+    fn into_response(self) -> Response {
+        (self, ()).into_response()
+    }
+}
+
+impl<K> Clone for ProtectedCookieJar<K> {
+    fn clone(&self) -> Self {
+        Self {
+            jar: self.jar.clone(),
+            key: self.key.clone(),
+            protection: self.protection.clone(),
+            _marker: self._marker,
+        }
+    }
+}
+
+/// A view onto a [`ProtectedCookieJar`] scoped to reading and writing signed cookies.
+///
+/// Obtained via [`ProtectedCookieJar::signed`]. Call [`SignedView::into_jar`] to get back the
+/// underlying jar, e.g. to switch to [`ProtectedCookieJar::private`] for a different cookie.
+pub struct SignedView<K = Key> {
+    jar: ProtectedCookieJar<K>,
+}
+
+impl<K> SignedView<K> {
+    /// Get a signed cookie from the jar.
+    pub fn get(&self, name: &str) -> Option<Cookie<'static>> {
+        self.jar.jar.signed(&self.jar.key).get(name)
+    }
+
+    /// Add a signed cookie to the jar.
+    ///
+    /// The value will automatically be percent-encoded.
+    #[must_use]
+    #[allow(clippy::should_implement_trait)]
+    pub fn add<C: Into<Cookie<'static>>>(mut self, cookie: C) -> Self {
+        let cookie = cookie.into();
+        self.jar
+            .protection
+            .insert(cookie.name().to_owned(), Protection::Signed);
+        self.jar.jar.signed_mut(&self.jar.key).add(cookie);
+        self
+    }
+
+    /// Remove a cookie from the jar.
+    #[must_use]
+    pub fn remove<C: Into<Cookie<'static>>>(mut self, cookie: C) -> Self {
+        let cookie = cookie.into();
+        self.jar.protection.remove(cookie.name());
+        self.jar.jar.signed_mut(&self.jar.key).remove(cookie);
+        self
+    }
+
+    /// Get back the underlying [`ProtectedCookieJar`].
+    pub fn into_jar(self) -> ProtectedCookieJar<K> {
+        self.jar
+    }
+}
+
+impl<K> From<SignedView<K>> for ProtectedCookieJar<K> {
+    fn from(view: SignedView<K>) -> Self {
+        view.into_jar()
+    }
+}
+
+/// A view onto a [`ProtectedCookieJar`] scoped to reading and writing private (encrypted)
+/// cookies.
+///
+/// Obtained via [`ProtectedCookieJar::private`]. Call [`PrivateView::into_jar`] to get back the
+/// underlying jar, e.g. to switch to [`ProtectedCookieJar::signed`] for a different cookie.
+pub struct PrivateView<K = Key> {
+    jar: ProtectedCookieJar<K>,
+}
+
+impl<K> PrivateView<K> {
+    /// Get a private cookie from the jar, decrypting it if present.
+    pub fn get(&self, name: &str) -> Option<Cookie<'static>> {
+        self.jar.jar.private(&self.jar.key).get(name)
+    }
+
+    /// Add an encrypted cookie to the jar.
+    ///
+    /// The value will automatically be percent-encoded.
+    #[must_use]
+    #[allow(clippy::should_implement_trait)]
+    pub fn add<C: Into<Cookie<'static>>>(mut self, cookie: C) -> Self {
+        let cookie = cookie.into();
+        self.jar
+            .protection
+            .insert(cookie.name().to_owned(), Protection::Private);
+        self.jar.jar.private_mut(&self.jar.key).add(cookie);
+        self
+    }
+
+    /// Remove a cookie from the jar.
+    #[must_use]
+    pub fn remove<C: Into<Cookie<'static>>>(mut self, cookie: C) -> Self {
+        let cookie = cookie.into();
+        self.jar.protection.remove(cookie.name());
+        self.jar.jar.private_mut(&self.jar.key).remove(cookie);
+        self
+    }
+
+    /// Get back the underlying [`ProtectedCookieJar`].
+    pub fn into_jar(self) -> ProtectedCookieJar<K> {
+        self.jar
+    }
+}
+
+impl<K> From<PrivateView<K>> for ProtectedCookieJar<K> {
+    fn from(view: PrivateView<K>) -> Self {
+        view.into_jar()
+    }
+}
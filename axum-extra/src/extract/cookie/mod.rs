@@ -1,6 +1,12 @@
 //! Cookie parsing and cookie jar management.
 //!
 //! See [`CookieJar`], [`SignedCookieJar`], and [`PrivateCookieJar`] for more details.
+//!
+//! For a jar that can be mutated in place without being returned from the handler, see
+//! [`Cookies`] and [`CookiesLayer`] (also available as [`CookieManagerLayer`]).
+//!
+//! For a typed key/value session built on top of an encrypted cookie, see [`Session`] and
+//! [`SessionLayer`].
 
 use axum::{
     extract::FromRequestParts,
@@ -11,23 +17,101 @@ use http::{
     request::Parts,
     HeaderMap,
 };
-use std::convert::Infallible;
+use std::{borrow::Cow, convert::Infallible};
+#[cfg(feature = "serde")]
+use std::fmt;
 
+#[cfg(all(feature = "cookie-signed", feature = "cookie-private"))]
+mod composite;
 #[cfg(feature = "cookie-private")]
 mod private;
+#[cfg(feature = "cookie-private")]
+mod session;
 #[cfg(feature = "cookie-signed")]
 mod signed;
+mod shared;
 
+#[cfg(all(feature = "cookie-signed", feature = "cookie-private"))]
+pub use self::composite::{Protection, ProtectedCookieJar, PrivateView, SignedView};
+#[cfg(feature = "cookie-private")]
+pub use self::private::{KeyRing, PrivateCookieJar};
 #[cfg(feature = "cookie-private")]
-pub use self::private::PrivateCookieJar;
+pub use self::session::{
+    BackendStore, CookieStore, Session, SessionBackend, SessionLayer, SessionLayerMissing,
+    SessionService, SessionStore, SessionStoreError, COOKIE_STORE_MAX_BYTES, SESSION_COOKIE_NAME,
+};
 #[cfg(feature = "cookie-signed")]
 pub use self::signed::SignedCookieJar;
+#[cfg(feature = "cookie-private")]
+pub use self::shared::PrivateCookies;
+#[cfg(feature = "cookie-signed")]
+pub use self::shared::SignedCookies;
+pub use self::shared::{
+    CookieManagerLayer, Cookies, CookiesLayer, CookiesLayerMissing, CookiesService,
+};
 
-pub use cookie::{Cookie, Expiration, SameSite};
+pub use cookie::{time::Duration, Cookie, Expiration, SameSite};
 
 #[cfg(any(feature = "cookie-signed", feature = "cookie-private"))]
 pub use cookie::Key;
 
+/// Default cookie attributes applied by [`CookieJar::with_defaults`] (and the matching
+/// constructors on [`PrivateCookieJar`]) to every cookie added to the jar that doesn't already
+/// set that attribute explicitly.
+///
+/// Any field left as `None` is left for the cookie (or the `cookie` crate's own defaults) to
+/// decide.
+#[derive(Debug, Clone, Default)]
+pub struct CookieDefaults {
+    /// Default `Path`, applied if the cookie doesn't set one.
+    pub path: Option<Cow<'static, str>>,
+    /// Default `Domain`, applied if the cookie doesn't set one.
+    pub domain: Option<Cow<'static, str>>,
+    /// Default `SameSite`, applied if the cookie doesn't set one.
+    pub same_site: Option<SameSite>,
+    /// Default `Secure`, applied if the cookie doesn't set one.
+    pub secure: Option<bool>,
+    /// Default `HttpOnly`, applied if the cookie doesn't set one.
+    pub http_only: Option<bool>,
+    /// Default `Max-Age`, applied if the cookie doesn't set one.
+    pub max_age: Option<Duration>,
+}
+
+impl CookieDefaults {
+    fn apply(&self, cookie: &mut Cookie<'static>) {
+        if cookie.path().is_none() {
+            if let Some(path) = self.path.clone() {
+                cookie.set_path(path);
+            }
+        }
+        if cookie.domain().is_none() {
+            if let Some(domain) = self.domain.clone() {
+                cookie.set_domain(domain);
+            }
+        }
+        if cookie.same_site().is_none() {
+            if let Some(same_site) = self.same_site {
+                cookie.set_same_site(same_site);
+            }
+        }
+        if cookie.secure().is_none() {
+            if let Some(secure) = self.secure {
+                cookie.set_secure(secure);
+            }
+        }
+        if cookie.http_only().is_none() {
+            if let Some(http_only) = self.http_only {
+                cookie.set_http_only(http_only);
+            }
+        }
+        if cookie.max_age().is_none() {
+            if let Some(max_age) = self.max_age {
+                cookie.set_max_age(max_age);
+            }
+        }
+    }
+}
+
 /// Extractor that grabs cookies from the request and manages the jar.
 ///
 /// Note that methods like [`CookieJar::add`], [`CookieJar::remove`], etc updates the [`CookieJar`]
@@ -87,6 +171,7 @@ pub use cookie::Key;
 #[derive(Debug, Default, Clone)]
 pub struct CookieJar {
     jar: cookie::CookieJar,
+    defaults: CookieDefaults,
 }
 
 impl<S> FromRequestParts<S> for CookieJar
@@ -123,7 +208,34 @@ impl CookieJar {
         for cookie in cookies_from_request(headers) {
             jar.add_original(cookie);
         }
-        Self { jar }
+        Self {
+            jar,
+            defaults: CookieDefaults::default(),
+        }
+    }
+
+    /// Set the default cookie attributes applied to every cookie subsequently added to this jar
+    /// that doesn't already set that attribute.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum_extra::extract::cookie::{Cookie, CookieDefaults, CookieJar, SameSite};
+    ///
+    /// async fn handler(jar: CookieJar) -> CookieJar {
+    ///     jar.with_defaults(CookieDefaults {
+    ///         path: Some("/".into()),
+    ///         same_site: Some(SameSite::Strict),
+    ///         secure: Some(true),
+    ///         ..Default::default()
+    ///     })
+    ///     .add(Cookie::new("foo", "bar"))
+    /// }
+    /// ```
+    #[must_use]
+    pub fn with_defaults(mut self, defaults: CookieDefaults) -> Self {
+        self.defaults = defaults;
+        self
     }
 
     /// Create a new empty `CookieJar`.
@@ -192,6 +304,8 @@ impl CookieJar {
     #[must_use]
     #[allow(clippy::should_implement_trait)]
     pub fn add<C: Into<Cookie<'static>>>(mut self, cookie: C) -> Self {
+        let mut cookie = cookie.into();
+        self.defaults.apply(&mut cookie);
         self.jar.add(cookie);
         self
     }
@@ -224,6 +338,8 @@ impl CookieJar {
         prefix: P,
         cookie: Cookie<'static>,
     ) -> Self {
+        let mut cookie = cookie;
+        self.defaults.apply(&mut cookie);
         let mut prefixed_jar = self.jar.prefixed_mut(prefix);
         prefixed_jar.add(cookie);
         self
@@ -276,8 +392,88 @@ impl CookieJar {
         prefixed_jar.remove(name.into());
         self
     }
+
+    /// Remove a cookie from the jar without recording a removal delta.
+    ///
+    /// Unlike [`CookieJar::remove`], this doesn't queue a `Set-Cookie: ...; Max-Age=0` header:
+    /// the cookie is simply dropped from this jar's own state. Useful for middleware that wants
+    /// to rewrite cookies before a handler ever sees them, without telling the browser to delete
+    /// anything.
+    #[must_use]
+    pub fn force_remove<C: Into<Cookie<'static>>>(mut self, cookie: C) -> Self {
+        self.jar.force_remove(cookie);
+        self
+    }
+
+    /// Queue a `Set-Cookie` that expires `name` in the browser (`Max-Age=0`), regardless of
+    /// whether this jar currently holds a cookie by that name.
+    ///
+    /// Uses the jar's default `Path` (see [`CookieJar::with_defaults`]) so the expiration lands
+    /// even in browsers that match `Set-Cookie` deletions against the original cookie's path.
+    #[must_use]
+    pub fn expire(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        let mut cookie = Cookie::new(name, "");
+        self.defaults.apply(&mut cookie);
+        cookie.set_max_age(Duration::ZERO);
+        self.jar.add(cookie);
+        self
+    }
+
+    /// Serialize the full cookie set (not just pending changes) to JSON.
+    ///
+    /// Useful for seeding a jar from a fixture file in integration tests, diffing jar state
+    /// between requests, or persisting a jar across process restarts. Restore with
+    /// [`CookieJar::from_json`].
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        let cookies: Vec<String> = self.jar.iter().map(|c| c.encoded().to_string()).collect();
+        serde_json::to_string(&cookies).expect("Vec<String> can always be serialized")
+    }
+
+    /// Restore a jar previously serialized with [`CookieJar::to_json`].
+    ///
+    /// Every cookie is added via `add_original`, so nothing is treated as a pending change until
+    /// the handler mutates it.
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, FromJsonError> {
+        let cookies: Vec<String> = serde_json::from_str(json).map_err(FromJsonError::Json)?;
+        let mut jar = cookie::CookieJar::new();
+        for encoded in cookies {
+            let cookie = Cookie::parse_encoded(encoded.clone())
+                .map_err(|_| FromJsonError::InvalidCookie(encoded))?;
+            jar.add_original(cookie);
+        }
+        Ok(Self {
+            jar,
+            defaults: CookieDefaults::default(),
+        })
+    }
+}
+
+/// Error returned by [`CookieJar::from_json`] when the input isn't a valid serialized jar.
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum FromJsonError {
+    /// The input wasn't valid JSON, or wasn't a JSON array of strings.
+    Json(serde_json::Error),
+    /// An entry in the array wasn't a valid `Set-Cookie`-style cookie string.
+    InvalidCookie(String),
+}
+
+#[cfg(feature = "serde")]
+impl fmt::Display for FromJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(err) => write!(f, "invalid cookie jar JSON: {err}"),
+            Self::InvalidCookie(cookie) => write!(f, "invalid cookie in jar JSON: {cookie:?}"),
+        }
+    }
 }
 
+#[cfg(feature = "serde")]
+impl std::error::Error for FromJsonError {}
+
 impl IntoResponseParts for CookieJar {
     type Error = Infallible;
 
@@ -310,6 +506,9 @@ mod tests {
     use axum::{body::Body, extract::FromRef, http::Request, routing::get, Router};
     use cookie::prefix::Host;
     use http_body_util::BodyExt;
+    #[cfg(feature = "cookie-private")]
+    use serde_json::{Map, Value};
+    use std::sync::{Arc, Mutex};
     use tower::ServiceExt;
 
     macro_rules! cookie_test {
@@ -499,6 +698,429 @@ mod tests {
         PrivateCookieJar<CustomKey>
     );
 
+    #[cfg(feature = "cookie-private")]
+    #[tokio::test]
+    async fn private_cookies_rotate_under_retired_key() {
+        let retired = Key::generate();
+        let primary = Key::generate();
+
+        // Encrypt a cookie under the key that's about to be retired.
+        let res = PrivateCookieJar::new(retired.clone())
+            .add(Cookie::new("key", "value"))
+            .into_response();
+        let stale_cookie = res.headers()["set-cookie"]
+            .to_str()
+            .unwrap()
+            .split(';')
+            .next()
+            .unwrap()
+            .to_owned();
+
+        let keys = KeyRing::with_previous(primary.clone(), vec![retired]);
+
+        // It still decrypts, via the retired key in `previous`...
+        let jar = PrivateCookieJar::from_headers(
+            &{
+                let mut headers = HeaderMap::new();
+                headers.insert("cookie", stale_cookie.parse().unwrap());
+                headers
+            },
+            keys,
+        );
+        assert_eq!(jar.get("key").unwrap().value(), "value");
+
+        // ...and gets re-encrypted under the primary key when written into a response, so a jar
+        // that only knows the primary key (not the retired one) can decrypt it afterwards.
+        let res = jar.into_response();
+        let reencrypted_cookie = res.headers()["set-cookie"]
+            .to_str()
+            .unwrap()
+            .split(';')
+            .next()
+            .unwrap()
+            .to_owned();
+        assert_ne!(reencrypted_cookie, stale_cookie);
+
+        let jar = PrivateCookieJar::from_headers(
+            &{
+                let mut headers = HeaderMap::new();
+                headers.insert("cookie", reencrypted_cookie.parse().unwrap());
+                headers
+            },
+            primary,
+        );
+        assert_eq!(jar.get("key").unwrap().value(), "value");
+    }
+
+    #[tokio::test]
+    async fn shared_cookies_mutate_in_place_without_being_returned() {
+        async fn handler(cookies: Cookies) -> &'static str {
+            // Unlike `CookieJar`, the handler can mutate the jar without returning it.
+            cookies.add(Cookie::new("key", "value"));
+            "ok"
+        }
+
+        let app = Router::new()
+            .route("/", get(handler))
+            .layer(CookiesLayer::new());
+
+        let res = app
+            .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        let cookie_value = res.headers()["set-cookie"].to_str().unwrap();
+        assert!(cookie_value.starts_with("key=value"));
+    }
+
+    #[cfg(feature = "cookie-private")]
+    #[tokio::test]
+    async fn private_cookies_with_defaults_sets_security_attributes() {
+        let jar = PrivateCookieJar::new(Key::generate())
+            .with_defaults(CookieDefaults {
+                path: Some("/".into()),
+                same_site: Some(SameSite::Strict),
+                secure: Some(true),
+                http_only: Some(true),
+                ..Default::default()
+            })
+            .add(Cookie::new("key", "value"));
+
+        let res = jar.into_response();
+        let cookie_value = res.headers()["set-cookie"].to_str().unwrap();
+
+        assert!(cookie_value.contains("Secure"));
+        assert!(cookie_value.contains("HttpOnly"));
+        assert!(cookie_value.contains("SameSite=Strict"));
+        assert!(cookie_value.contains("Path=/"));
+    }
+
+    #[tokio::test]
+    async fn cookie_jar_with_defaults_sets_security_attributes() {
+        let jar = CookieJar::new()
+            .with_defaults(CookieDefaults {
+                path: Some("/".into()),
+                same_site: Some(SameSite::Strict),
+                secure: Some(true),
+                http_only: Some(true),
+                ..Default::default()
+            })
+            .add(Cookie::new("key", "value"));
+
+        let res = jar.into_response();
+        let cookie_value = res.headers()["set-cookie"].to_str().unwrap();
+
+        assert!(cookie_value.contains("Secure"));
+        assert!(cookie_value.contains("HttpOnly"));
+        assert!(cookie_value.contains("SameSite=Strict"));
+        assert!(cookie_value.contains("Path=/"));
+    }
+
+    #[cfg(feature = "cookie-signed")]
+    #[tokio::test]
+    async fn signed_cookies_with_defaults_sets_security_attributes() {
+        let jar = SignedCookieJar::new(Key::generate())
+            .with_defaults(CookieDefaults {
+                path: Some("/".into()),
+                same_site: Some(SameSite::Strict),
+                secure: Some(true),
+                http_only: Some(true),
+                ..Default::default()
+            })
+            .add(Cookie::new("key", "value"));
+
+        let res = jar.into_response();
+        let cookie_value = res.headers()["set-cookie"].to_str().unwrap();
+
+        assert!(cookie_value.contains("Secure"));
+        assert!(cookie_value.contains("HttpOnly"));
+        assert!(cookie_value.contains("SameSite=Strict"));
+        assert!(cookie_value.contains("Path=/"));
+    }
+
+    #[cfg(all(feature = "cookie-signed", feature = "cookie-private"))]
+    #[tokio::test]
+    async fn protected_jar_selects_protection_per_cookie() {
+        async fn set_cookies(jar: ProtectedCookieJar) -> ProtectedCookieJar {
+            jar.signed()
+                .add(Cookie::new("csrf_token", "readable"))
+                .into_jar()
+                .private()
+                .add(Cookie::new("session", "secret"))
+                .into_jar()
+        }
+
+        async fn get_cookies(jar: ProtectedCookieJar) -> String {
+            format!(
+                "{:?} {:?}",
+                jar.get("csrf_token").map(|c| c.value().to_owned()),
+                jar.get("session").map(|c| c.value().to_owned()),
+            )
+        }
+
+        let state = AppState {
+            key: Key::generate(),
+            custom_key: CustomKey(Key::generate()),
+        };
+
+        let app = Router::new()
+            .route("/set", get(set_cookies))
+            .route("/get", get(get_cookies))
+            .with_state(state);
+
+        let res = app
+            .clone()
+            .oneshot(Request::builder().uri("/set").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let cookie_header = res.headers().get_all("set-cookie");
+        let cookie_header = cookie_header
+            .iter()
+            .map(|v| v.to_str().unwrap())
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        // The signed cookie's value is readable in plaintext after its signature prefix; the
+        // private one is not.
+        assert!(cookie_header.contains("readable"));
+        assert!(!cookie_header.contains("session=secret"));
+
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .uri("/get")
+                    .header("cookie", cookie_header)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = body_text(res).await;
+        assert_eq!(body, r#"Some("readable") Some("secret")"#);
+    }
+
+    #[tokio::test]
+    async fn cookie_manager_layer_is_usable_as_cookies_layer() {
+        async fn handler(cookies: Cookies) -> &'static str {
+            cookies.add(Cookie::new("key", "value"));
+            "ok"
+        }
+
+        let app = Router::new()
+            .route("/", get(handler))
+            .layer(CookieManagerLayer::new());
+
+        let res = app
+            .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        let cookie_value = res.headers()["set-cookie"].to_str().unwrap();
+        assert!(cookie_value.starts_with("key=value"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn jar_json_round_trips_values_needing_percent_encoding() {
+        let mut jar = CookieJar::new();
+        jar = jar.add(Cookie::new("key", "a value; with, special chars"));
+
+        let json = jar.to_json();
+        let restored = CookieJar::from_json(&json).unwrap();
+
+        assert_eq!(
+            restored.get("key").unwrap().value(),
+            "a value; with, special chars"
+        );
+    }
+
+    #[tokio::test]
+    async fn force_remove_drops_cookie_without_queuing_a_set_cookie() {
+        let jar = CookieJar::new()
+            .add(Cookie::new("key", "value"))
+            .force_remove(Cookie::from("key"));
+
+        assert!(jar.get("key").is_none());
+        let res = jar.into_response();
+        assert!(!res.headers().contains_key("set-cookie"));
+    }
+
+    #[tokio::test]
+    async fn expire_queues_a_set_cookie_even_if_absent_from_the_jar() {
+        let jar = CookieJar::new().expire("key");
+
+        let res = jar.into_response();
+        let cookie_value = res.headers()["set-cookie"].to_str().unwrap();
+        assert!(cookie_value.starts_with("key="));
+        assert!(cookie_value.contains("Max-Age=0"));
+    }
+
+    #[cfg(feature = "cookie-private")]
+    #[tokio::test]
+    async fn session_only_sets_cookie_when_mutated() {
+        async fn noop(_session: Session) {}
+
+        async fn set_and_get(session: Session) -> String {
+            session.insert("n", 1);
+            session.get::<i32>("n").unwrap().to_string()
+        }
+
+        let app = Router::new()
+            .route("/noop", get(noop))
+            .route("/set", get(set_and_get))
+            .layer(SessionLayer::new(Key::generate()));
+
+        let res = app
+            .clone()
+            .oneshot(Request::builder().uri("/noop").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert!(!res.headers().contains_key("set-cookie"));
+
+        let res = app
+            .clone()
+            .oneshot(Request::builder().uri("/set").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let cookie_header = res.headers()["set-cookie"]
+            .to_str()
+            .unwrap()
+            .split(';')
+            .next()
+            .unwrap()
+            .to_owned();
+        assert_eq!(body_text(res).await, "1");
+
+        // The same session data is readable on the next request via the cookie just issued.
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .uri("/set")
+                    .header("cookie", cookie_header)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(body_text(res).await, "1");
+    }
+
+    #[cfg(feature = "cookie-private")]
+    #[tokio::test]
+    async fn cookie_store_rejects_oversized_session_with_500() {
+        async fn bloat(session: Session) {
+            session.insert("blob", "x".repeat(COOKIE_STORE_MAX_BYTES));
+        }
+
+        let app = Router::new()
+            .route("/", get(bloat))
+            .layer(SessionLayer::new(Key::generate()));
+
+        let res = app
+            .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), http::StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(!res.headers().contains_key("set-cookie"));
+    }
+
+    #[cfg(feature = "cookie-private")]
+    #[derive(Clone, Default)]
+    struct InMemoryBackend {
+        ids: Arc<Mutex<Vec<String>>>,
+        destroyed: Arc<Mutex<Vec<String>>>,
+        data: Arc<Mutex<std::collections::HashMap<String, Map<String, Value>>>>,
+    }
+
+    #[cfg(feature = "cookie-private")]
+    impl SessionBackend for InMemoryBackend {
+        async fn get(&self, id: &str) -> Option<Map<String, Value>> {
+            self.data.lock().unwrap().get(id).cloned()
+        }
+
+        async fn set(&self, id: &str, data: Map<String, Value>) {
+            self.ids.lock().unwrap().push(id.to_owned());
+            self.data.lock().unwrap().insert(id.to_owned(), data);
+        }
+
+        async fn remove(&self, id: &str) {
+            self.destroyed.lock().unwrap().push(id.to_owned());
+            self.data.lock().unwrap().remove(id);
+        }
+    }
+
+    #[cfg(feature = "cookie-private")]
+    #[tokio::test]
+    async fn backend_store_reuses_id_unless_renewed() {
+        async fn mutate(session: Session) {
+            session.insert("n", 1);
+        }
+
+        async fn login(session: Session) {
+            session.insert("n", 1);
+            session.renew();
+        }
+
+        let backend = InMemoryBackend::default();
+        let app = Router::new()
+            .route("/mutate", get(mutate))
+            .route("/login", get(login))
+            .layer(SessionLayer::with_store(
+                Key::generate(),
+                BackendStore::new(backend.clone()),
+            ));
+
+        let res = app
+            .clone()
+            .oneshot(Request::builder().uri("/mutate").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let cookie_header = res.headers()["set-cookie"]
+            .to_str()
+            .unwrap()
+            .split(';')
+            .next()
+            .unwrap()
+            .to_owned();
+
+        let _res = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/mutate")
+                    .header("cookie", cookie_header.clone())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        // Ordinary mutation doesn't rotate the id: the backend only ever saw one id.
+        {
+            let ids = backend.ids.lock().unwrap();
+            assert_eq!(ids.len(), 2);
+            assert_eq!(ids[0], ids[1]);
+        }
+
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .uri("/login")
+                    .header("cookie", cookie_header)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(res.headers().contains_key("set-cookie"));
+
+        let ids = backend.ids.lock().unwrap();
+        let first_id = ids[0].clone();
+        let last_id = ids.last().unwrap().clone();
+        assert_ne!(first_id, last_id);
+        assert_eq!(&*backend.destroyed.lock().unwrap(), &[first_id]);
+    }
+
     #[derive(Clone)]
     struct AppState {
         key: Key,
@@ -1,11 +1,63 @@
-use super::{cookies_from_request, set_cookies, Cookie, Key};
+use super::{cookies_from_request, set_cookies, Cookie, CookieDefaults, Duration, Key};
 use axum::{
     extract::{FromRef, FromRequestParts},
     response::{IntoResponse, IntoResponseParts, Response, ResponseParts},
 };
 use cookie::PrivateJar;
 use http::{request::Parts, HeaderMap};
-use std::{convert::Infallible, fmt, marker::PhantomData};
+use std::{collections::HashSet, convert::Infallible, fmt, marker::PhantomData};
+
+/// A set of keys used by [`PrivateCookieJar`] to support key rotation.
+///
+/// The `primary` key is used to encrypt new cookies and is always tried first when decrypting
+/// incoming ones. Cookies that only decrypt successfully under one of the `previous` keys are
+/// still accepted, but are flagged so the jar can transparently re-encrypt them under the
+/// primary key the next time it's written into a response. This lets an application roll its
+/// signing/encryption key without invalidating cookies that are already out in the wild.
+#[derive(Clone)]
+pub struct KeyRing {
+    primary: Key,
+    previous: Vec<Key>,
+}
+
+impl KeyRing {
+    /// Create a `KeyRing` with no retired keys.
+    pub fn new(primary: Key) -> Self {
+        Self {
+            primary,
+            previous: Vec::new(),
+        }
+    }
+
+    /// Create a `KeyRing` whose `primary` key is used for encryption, falling back to `previous`
+    /// keys, in order, when decrypting a cookie that the primary key rejects.
+    pub fn with_previous(primary: Key, previous: Vec<Key>) -> Self {
+        Self { primary, previous }
+    }
+
+    /// Try to decrypt `cookie` with the primary key, then each previous key in order.
+    ///
+    /// Returns the plaintext cookie along with whether it was decrypted using a previous
+    /// (non-primary) key.
+    fn decrypt(&self, cookie: Cookie<'static>) -> Option<(Cookie<'static>, bool)> {
+        let dummy = cookie::CookieJar::new();
+
+        if let Some(plaintext) = dummy.private(&self.primary).decrypt(cookie.clone()) {
+            return Some((plaintext, false));
+        }
+
+        self.previous
+            .iter()
+            .find_map(|key| dummy.private(key).decrypt(cookie.clone()))
+            .map(|plaintext| (plaintext, true))
+    }
+}
+
+impl<T: Into<Key>> From<T> for KeyRing {
+    fn from(primary: T) -> Self {
+        Self::new(primary.into())
+    }
+}
 
 /// Extractor that grabs private cookies from the request and manages the jar.
 ///
@@ -104,9 +156,21 @@ use std::{convert::Infallible, fmt, marker::PhantomData};
 ///     }
 /// }
 /// ```
+///
+/// # Key rotation
+///
+/// To roll the encryption key without logging out everyone holding a cookie encrypted under the
+/// old one, supply a [`KeyRing`] (via `FromRef`, or directly through [`PrivateCookieJar::with_rotation`])
+/// instead of a bare [`Key`]. Cookies that only decrypt under a retired key are accepted and
+/// transparently re-encrypted under the primary key the next time the jar is returned from a
+/// handler.
 pub struct PrivateCookieJar<K = Key> {
     jar: cookie::CookieJar,
-    key: Key,
+    keys: KeyRing,
+    // Names of cookies that were decrypted using a non-primary key. These are re-added under the
+    // primary key when the jar is written into a response, so rotation is transparent.
+    stale: HashSet<String>,
+    defaults: CookieDefaults,
     // The key used to extract the key. Allows users to use multiple keys for different
     // jars. Maybe a library wants its own key.
     _marker: PhantomData<K>,
@@ -116,7 +180,7 @@ impl<K> fmt::Debug for PrivateCookieJar<K> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("PrivateCookieJar")
             .field("jar", &self.jar)
-            .field("key", &"REDACTED")
+            .field("keys", &"REDACTED")
             .finish()
     }
 }
@@ -124,21 +188,25 @@ impl<K> fmt::Debug for PrivateCookieJar<K> {
 impl<S, K> FromRequestParts<S> for PrivateCookieJar<K>
 where
     S: Send + Sync,
-    K: FromRef<S> + Into<Key>,
+    K: FromRef<S> + Into<KeyRing>,
 {
     type Rejection = Infallible;
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
         let k = K::from_ref(state);
-        let key = k.into();
+        let keys = k.into();
         let PrivateCookieJar {
             jar,
-            key,
+            keys,
+            stale,
+            defaults,
             _marker: _,
-        } = PrivateCookieJar::from_headers(&parts.headers, key);
+        } = PrivateCookieJar::from_headers(&parts.headers, keys);
         Ok(PrivateCookieJar {
             jar,
-            key,
+            keys,
+            stale,
+            defaults,
             _marker: PhantomData,
         })
     }
@@ -147,24 +215,34 @@ where
 impl PrivateCookieJar {
     /// Create a new `PrivateCookieJar` from a map of request headers.
     ///
-    /// The valid cookies in `headers` will be added to the jar.
+    /// The valid cookies in `headers` will be added to the jar. A cookie that only decrypts
+    /// under a previous (non-primary) key of `keys` is still added, but is flagged as stale so
+    /// it gets re-encrypted under the primary key on the next response.
     ///
     /// This is intended to be used in middleware and other where places it might be difficult to
     /// run extractors. Normally you should create `PrivateCookieJar`s through [`FromRequestParts`].
     ///
     /// [`FromRequestParts`]: axum::extract::FromRequestParts
-    pub fn from_headers(headers: &HeaderMap, key: Key) -> Self {
+    pub fn from_headers(headers: &HeaderMap, keys: impl Into<KeyRing>) -> Self {
+        let keys = keys.into();
         let mut jar = cookie::CookieJar::new();
-        let mut private_jar = jar.private_mut(&key);
+        let mut stale = HashSet::new();
+
         for cookie in cookies_from_request(headers) {
-            if let Some(cookie) = private_jar.decrypt(cookie) {
-                private_jar.add_original(cookie);
+            if let Some((plaintext, is_stale)) = keys.decrypt(cookie) {
+                let name = plaintext.name().to_owned();
+                jar.private_mut(&keys.primary).add_original(plaintext);
+                if is_stale {
+                    stale.insert(name);
+                }
             }
         }
 
         Self {
             jar,
-            key,
+            keys,
+            stale,
+            defaults: CookieDefaults::default(),
             _marker: PhantomData,
         }
     }
@@ -175,13 +253,25 @@ impl PrivateCookieJar {
     /// run extractors. Normally you should create `PrivateCookieJar`s through [`FromRequestParts`].
     ///
     /// [`FromRequestParts`]: axum::extract::FromRequestParts
-    pub fn new(key: Key) -> Self {
+    pub fn new(keys: impl Into<KeyRing>) -> Self {
         Self {
             jar: Default::default(),
-            key,
+            keys: keys.into(),
+            stale: HashSet::new(),
+            defaults: CookieDefaults::default(),
             _marker: PhantomData,
         }
     }
+
+    /// Create a new empty `PrivateCookieJar` that accepts cookies encrypted under `primary` or,
+    /// failing that, any key in `previous`.
+    ///
+    /// This is a convenience over [`PrivateCookieJar::new`] for the common case of rotating a
+    /// single key. Cookies that only validate under a previous key are re-encrypted under
+    /// `primary` the next time the jar is written into a response.
+    pub fn with_rotation(primary: Key, previous: Vec<Key>) -> Self {
+        Self::new(KeyRing::with_previous(primary, previous))
+    }
 }
 
 impl<K> PrivateCookieJar<K> {
@@ -219,6 +309,8 @@ impl<K> PrivateCookieJar<K> {
     /// ```
     #[must_use]
     pub fn remove<C: Into<Cookie<'static>>>(mut self, cookie: C) -> Self {
+        let cookie = cookie.into();
+        self.stale.remove(cookie.name());
         self.private_jar_mut().remove(cookie);
         self
     }
@@ -240,14 +332,42 @@ impl<K> PrivateCookieJar<K> {
     #[must_use]
     #[allow(clippy::should_implement_trait)]
     pub fn add<C: Into<Cookie<'static>>>(mut self, cookie: C) -> Self {
+        let mut cookie = cookie.into();
+        self.defaults.apply(&mut cookie);
+        self.stale.remove(cookie.name());
         self.private_jar_mut().add(cookie);
         self
     }
 
+    /// Set default cookie attributes that are applied to every cookie added to this jar (via
+    /// [`PrivateCookieJar::add`] or [`PrivateCookieJar::add_prefixed`]) that doesn't already set
+    /// that attribute explicitly.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum_extra::extract::cookie::{CookieDefaults, PrivateCookieJar, SameSite};
+    ///
+    /// async fn handle(jar: PrivateCookieJar) -> PrivateCookieJar {
+    ///     jar.with_defaults(CookieDefaults {
+    ///         path: Some("/".into()),
+    ///         same_site: Some(SameSite::Strict),
+    ///         secure: Some(true),
+    ///         ..Default::default()
+    ///     })
+    /// }
+    /// ```
+    #[must_use]
+    pub fn with_defaults(mut self, defaults: CookieDefaults) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
     /// Authenticates and decrypts `cookie`, returning the plaintext version if decryption succeeds
-    /// or `None` otherwise.
+    /// under the primary key or any retired key in [`PrivateCookieJar::with_rotation`]'s `previous`
+    /// list, or `None` otherwise.
     pub fn decrypt(&self, cookie: Cookie<'static>) -> Option<Cookie<'static>> {
-        self.private_jar().decrypt(cookie.clone())
+        self.keys.decrypt(cookie).map(|(plaintext, _)| plaintext)
     }
 
     /// Get an iterator over all cookies in the jar.
@@ -261,11 +381,11 @@ impl<K> PrivateCookieJar<K> {
     }
 
     fn private_jar(&self) -> PrivateJar<&'_ cookie::CookieJar> {
-        self.jar.private(&self.key)
+        self.jar.private(&self.keys.primary)
     }
 
     fn private_jar_mut(&mut self) -> PrivateJar<&'_ mut cookie::CookieJar> {
-        self.jar.private_mut(&self.key)
+        self.jar.private_mut(&self.keys.primary)
     }
     /// Add a signed cookie with the specified prefix to the jar.
     ///
@@ -292,12 +412,18 @@ impl<K> PrivateCookieJar<K> {
 
         let prefixed_name = format!("{}{}", P::PREFIX, cookie.name());
         let mut new_cookie = cookie;
-        new_cookie.set_name(prefixed_name);
-        jar.private_mut(&self.key).add(new_cookie);
+        self.defaults.apply(&mut new_cookie);
+        new_cookie.set_name(prefixed_name.clone());
+        jar.private_mut(&self.keys.primary).add(new_cookie);
+
+        let mut stale = self.stale;
+        stale.remove(&prefixed_name);
 
         Self {
             jar,
-            key: self.key,
+            keys: self.keys,
+            stale,
+            defaults: self.defaults,
             _marker: self._marker,
         }
     }
@@ -347,12 +473,52 @@ impl<K> PrivateCookieJar<K> {
         prefixed_jar.remove(name.into());
         self
     }
+
+    /// Remove a cookie from the jar without recording a removal delta.
+    ///
+    /// Unlike [`PrivateCookieJar::remove`], this doesn't queue a `Set-Cookie: ...; Max-Age=0`
+    /// header: the cookie is simply dropped from this jar's own state. Useful for middleware
+    /// that wants to rewrite cookies before a handler ever sees them, without telling the
+    /// browser to delete anything.
+    #[must_use]
+    pub fn force_remove<C: Into<Cookie<'static>>>(mut self, cookie: C) -> Self {
+        let cookie = cookie.into();
+        self.stale.remove(cookie.name());
+        self.jar.force_remove(cookie);
+        self
+    }
+
+    /// Queue a `Set-Cookie` that expires `name` in the browser (`Max-Age=0`), regardless of
+    /// whether this jar currently holds a cookie by that name.
+    ///
+    /// Uses the jar's default `Path` (see [`PrivateCookieJar::with_defaults`]) so the expiration
+    /// lands even in browsers that match `Set-Cookie` deletions against the original cookie's
+    /// path. The expiration cookie carries no data, so it's written in plaintext rather than
+    /// being encrypted.
+    #[must_use]
+    pub fn expire(mut self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        self.stale.remove(&name);
+        let mut cookie = Cookie::new(name, "");
+        self.defaults.apply(&mut cookie);
+        cookie.set_max_age(Duration::ZERO);
+        self.jar.add(cookie);
+        self
+    }
 }
 
 impl<K> IntoResponseParts for PrivateCookieJar<K> {
     type Error = Infallible;
 
-    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+    fn into_response_parts(mut self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        // Cookies that only decrypted under a retired key are re-encrypted under the primary key
+        // here, so rotation is transparent to the rest of the response pipeline.
+        for name in std::mem::take(&mut self.stale) {
+            if let Some(cookie) = self.get(&name) {
+                self.private_jar_mut().add(cookie);
+            }
+        }
+
         set_cookies(self.jar, res.headers_mut());
         Ok(res)
     }
@@ -387,7 +553,9 @@ impl<K> Clone for PrivateCookieJar<K> {
     fn clone(&self) -> Self {
         Self {
             jar: self.jar.clone(),
-            key: self.key.clone(),
+            keys: self.keys.clone(),
+            stale: self.stale.clone(),
+            defaults: self.defaults.clone(),
             _marker: self._marker,
         }
     }
@@ -0,0 +1,458 @@
+//! Typed, dirty-tracked session storage layered on top of an encrypted cookie.
+
+use super::{set_cookies, Cookie, CookieDefaults, Key, PrivateCookieJar, SameSite};
+use axum::{
+    extract::FromRequestParts,
+    response::{IntoResponse, Response},
+};
+use http::{request::Parts, Request, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Cookie name a [`Session`] is stored under.
+pub const SESSION_COOKIE_NAME: &str = "axum.session";
+
+/// Maximum size, in bytes, of the JSON payload [`CookieStore`] will accept in a single cookie.
+pub const COOKIE_STORE_MAX_BYTES: usize = 4096;
+
+struct SharedSession {
+    data: Mutex<Map<String, Value>>,
+    dirty: Mutex<bool>,
+    renew: Mutex<bool>,
+}
+
+/// A typed, JSON-backed session.
+///
+/// Values are read and written by key, similar to a `HashMap<String, serde_json::Value>`, but
+/// typed: [`Session::get`] deserializes into any `T: DeserializeOwned` and [`Session::insert`]
+/// serializes any `T: Serialize`. Unlike [`PrivateCookieJar`](super::PrivateCookieJar), `Session`
+/// does not need to be returned from the handler: [`SessionLayer`] persists whatever changes were
+/// made, and only writes a `Set-Cookie` header if the session was actually mutated.
+///
+/// [`SessionLayer`] must be added to the router for the `Session` extractor to work.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{extract::FromRef, routing::get, Router};
+/// use axum_extra::extract::cookie::{Key, Session, SessionLayer};
+///
+/// async fn login(session: Session) {
+///     session.insert("user_id", 42);
+/// }
+///
+/// async fn me(session: Session) -> String {
+///     session.get::<u64>("user_id").map_or_else(String::new, |id| id.to_string())
+/// }
+///
+/// #[derive(Clone)]
+/// struct AppState {
+///     key: Key,
+/// }
+///
+/// impl FromRef<AppState> for Key {
+///     fn from_ref(state: &AppState) -> Self {
+///         state.key.clone()
+///     }
+/// }
+///
+/// let state = AppState { key: Key::generate() };
+/// let app = Router::new()
+///     .route("/login", get(login))
+///     .route("/me", get(me))
+///     .layer(SessionLayer::new(state.key.clone()))
+///     .with_state(state);
+/// # let _: axum::Router = app;
+/// ```
+#[derive(Clone)]
+pub struct Session {
+    shared: Arc<SharedSession>,
+}
+
+impl Session {
+    /// Get a value from the session, deserializing it into `T`.
+    ///
+    /// Returns `None` if the key is missing or doesn't deserialize into `T`.
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.shared
+            .data
+            .lock()
+            .unwrap()
+            .get(key)
+            .cloned()
+            .and_then(|value| serde_json::from_value(value).ok())
+    }
+
+    /// Insert a value into the session, overwriting any existing value for `key`.
+    ///
+    /// Does nothing if `value` fails to serialize.
+    pub fn insert<T: Serialize>(&self, key: impl Into<String>, value: T) {
+        if let Ok(value) = serde_json::to_value(value) {
+            self.shared.data.lock().unwrap().insert(key.into(), value);
+            *self.shared.dirty.lock().unwrap() = true;
+        }
+    }
+
+    /// Remove a single value from the session.
+    pub fn remove(&self, key: &str) {
+        if self.shared.data.lock().unwrap().remove(key).is_some() {
+            *self.shared.dirty.lock().unwrap() = true;
+        }
+    }
+
+    /// Remove every value from the session.
+    pub fn clear(&self) {
+        let mut data = self.shared.data.lock().unwrap();
+        if !data.is_empty() {
+            data.clear();
+            *self.shared.dirty.lock().unwrap() = true;
+        }
+    }
+
+    /// Whether the session has been mutated since it was loaded.
+    pub fn is_dirty(&self) -> bool {
+        *self.shared.dirty.lock().unwrap()
+    }
+
+    /// Force a fresh, unguessable session identifier on the next save, destroying the old one.
+    ///
+    /// Call this after any action that changes the session's privilege level (e.g. logging a
+    /// user in), so that an attacker who knew the pre-login session id can't reuse it
+    /// post-login. Ordinary field mutations ([`Session::insert`], [`Session::remove`], etc)
+    /// persist under the existing id; only `renew` rotates it.
+    pub fn renew(&self) {
+        *self.shared.renew.lock().unwrap() = true;
+        *self.shared.dirty.lock().unwrap() = true;
+    }
+}
+
+impl<S> FromRequestParts<S> for Session
+where
+    S: Send + Sync,
+{
+    type Rejection = SessionLayerMissing;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Arc<SharedSession>>()
+            .cloned()
+            .map(|shared| Session { shared })
+            .ok_or(SessionLayerMissing)
+    }
+}
+
+/// Rejection used if [`Session`] is extracted without [`SessionLayer`] having been added to the
+/// router.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct SessionLayerMissing;
+
+impl fmt::Display for SessionLayerMissing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`Session` extractor used without adding `SessionLayer` to the router"
+        )
+    }
+}
+
+impl std::error::Error for SessionLayerMissing {}
+
+impl IntoResponse for SessionLayerMissing {
+    fn into_response(self) -> Response {
+        (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()).into_response()
+    }
+}
+
+/// Error returned when a [`SessionStore`] fails to persist session data.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SessionStoreError {
+    /// The session payload was too large for this store to accept.
+    TooLarge,
+}
+
+impl fmt::Display for SessionStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooLarge => write!(f, "session payload too large for this store"),
+        }
+    }
+}
+
+impl std::error::Error for SessionStoreError {}
+
+/// A pluggable backend for persisting [`Session`] data out of band from the cookie itself.
+///
+/// [`SessionLayer`] calls [`SessionStore::save`] to turn the session's data into an opaque
+/// token, which is what actually gets stored (encrypted) in the session cookie; on the next
+/// request, [`SessionStore::load`] turns that token back into session data.
+///
+/// See [`CookieStore`] for the stateless, cookie-only implementation, and [`BackendStore`] for
+/// storing the data server-side behind an opaque session id.
+pub trait SessionStore: Clone + Send + Sync + 'static {
+    /// Persist `data` and return the opaque token to store in the session cookie.
+    ///
+    /// `previous_token` is the token the request came in with, if any. `renew` is set when
+    /// [`Session::renew`] was called, meaning the caller wants a fresh, unguessable token even
+    /// if one already existed; implementations that hand out identifiers (like [`BackendStore`])
+    /// should otherwise keep reusing `previous_token` so ordinary mutations don't rotate it.
+    fn save(
+        &self,
+        data: &Map<String, Value>,
+        previous_token: Option<&str>,
+        renew: bool,
+    ) -> impl Future<Output = Result<String, SessionStoreError>> + Send;
+
+    /// Resolve a token previously returned by [`SessionStore::save`] back into session data.
+    ///
+    /// Returns `None` if the token is missing, expired, or unknown.
+    fn load(&self, token: &str) -> impl Future<Output = Option<Map<String, Value>>> + Send;
+
+    /// Invalidate a token, e.g. because the session was rotated or logged out, so it can no
+    /// longer be resolved.
+    fn destroy(&self, token: &str) -> impl Future<Output = ()> + Send;
+}
+
+/// A [`SessionStore`] that keeps the entire session payload in the cookie itself, encoded as
+/// JSON.
+///
+/// Simple and requires no server-side storage, at the cost of sending the full session payload
+/// on every request and a hard size limit (see [`COOKIE_STORE_MAX_BYTES`]).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CookieStore;
+
+impl SessionStore for CookieStore {
+    async fn save(
+        &self,
+        data: &Map<String, Value>,
+        _previous_token: Option<&str>,
+        _renew: bool,
+    ) -> Result<String, SessionStoreError> {
+        let token = serde_json::to_string(data).unwrap_or_default();
+        if token.len() > COOKIE_STORE_MAX_BYTES {
+            return Err(SessionStoreError::TooLarge);
+        }
+        Ok(token)
+    }
+
+    async fn load(&self, token: &str) -> Option<Map<String, Value>> {
+        serde_json::from_str(token).ok()
+    }
+
+    async fn destroy(&self, _token: &str) {}
+}
+
+/// A server-side backend that a [`BackendStore`] persists session data against, keyed by an
+/// opaque session id.
+///
+/// Implement this for whatever storage an application already has (Redis, a database table, an
+/// in-process map, ...).
+pub trait SessionBackend: Clone + Send + Sync + 'static {
+    /// Fetch session data by id.
+    fn get(&self, id: &str) -> impl Future<Output = Option<Map<String, Value>>> + Send;
+
+    /// Insert or overwrite session data by id.
+    fn set(&self, id: &str, data: Map<String, Value>) -> impl Future<Output = ()> + Send;
+
+    /// Delete session data by id.
+    fn remove(&self, id: &str) -> impl Future<Output = ()> + Send;
+}
+
+/// A [`SessionStore`] that keeps only an opaque, randomly generated session id in the cookie,
+/// storing the actual session data in a user-supplied [`SessionBackend`].
+///
+/// A fresh id is generated the first time a session is saved, and reused for every subsequent
+/// mutation under that id. Call [`Session::renew`] (typically right after logging a user in) to
+/// force a new id and destroy the old one, preventing session fixation without rotating the id
+/// on every ordinary field write.
+#[derive(Debug, Clone)]
+pub struct BackendStore<B> {
+    backend: B,
+}
+
+impl<B> BackendStore<B> {
+    /// Wrap `backend` as a [`SessionStore`].
+    pub fn new(backend: B) -> Self {
+        Self { backend }
+    }
+}
+
+impl<B: SessionBackend> SessionStore for BackendStore<B> {
+    async fn save(
+        &self,
+        data: &Map<String, Value>,
+        previous_token: Option<&str>,
+        renew: bool,
+    ) -> Result<String, SessionStoreError> {
+        if let (Some(id), false) = (previous_token, renew) {
+            self.backend.set(id, data.clone()).await;
+            return Ok(id.to_owned());
+        }
+
+        let id = generate_session_id();
+        self.backend.set(&id, data.clone()).await;
+        Ok(id)
+    }
+
+    async fn load(&self, token: &str) -> Option<Map<String, Value>> {
+        self.backend.get(token).await
+    }
+
+    async fn destroy(&self, token: &str) {
+        self.backend.remove(token).await;
+    }
+}
+
+/// Generate a random, opaque session id for [`BackendStore`].
+///
+/// Reuses the `cookie` crate's own CSPRNG (via a throwaway [`Key`]) for the random bytes rather
+/// than pulling in a separate random-number dependency just for this.
+fn generate_session_id() -> String {
+    use std::fmt::Write;
+
+    let key = Key::generate();
+    let master = key.master();
+    master
+        .iter()
+        .fold(String::with_capacity(master.len() * 2), |mut out, byte| {
+            let _ = write!(out, "{byte:02x}");
+            out
+        })
+}
+
+/// A [`tower::Layer`] that loads a [`Session`] before the handler runs and persists it, via a
+/// [`SessionStore`], after the handler returns, only writing a `Set-Cookie` header if the session
+/// was actually mutated.
+///
+/// Must be added to the router for the [`Session`] extractor to work; see [`Session`] for an
+/// example.
+///
+/// [`tower::Layer`]: https://docs.rs/tower/latest/tower/trait.Layer.html
+#[derive(Clone)]
+pub struct SessionLayer<Store = CookieStore> {
+    key: Key,
+    store: Store,
+}
+
+impl SessionLayer<CookieStore> {
+    /// Create a `SessionLayer` that keeps the whole session in the cookie itself.
+    pub fn new(key: Key) -> Self {
+        Self {
+            key,
+            store: CookieStore,
+        }
+    }
+}
+
+impl<Store> SessionLayer<Store> {
+    /// Create a `SessionLayer` backed by a custom [`SessionStore`], e.g. a [`BackendStore`].
+    pub fn with_store(key: Key, store: Store) -> Self {
+        Self { key, store }
+    }
+}
+
+impl<Store: Clone, S> Layer<S> for SessionLayer<Store> {
+    type Service = SessionService<Store, S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SessionService {
+            inner,
+            key: self.key.clone(),
+            store: self.store.clone(),
+        }
+    }
+}
+
+/// The [`Service`] produced by [`SessionLayer`]. See that type for details.
+#[derive(Clone)]
+pub struct SessionService<Store, S> {
+    inner: S,
+    key: Key,
+    store: Store,
+}
+
+impl<Store, S, ReqBody> Service<Request<ReqBody>> for SessionService<Store, S>
+where
+    Store: SessionStore,
+    S: Service<Request<ReqBody>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        let key = self.key.clone();
+        let store = self.store.clone();
+        let mut inner = self.inner.clone();
+
+        Box::pin(async move {
+            let old_token = PrivateCookieJar::from_headers(req.headers(), key.clone())
+                .get(SESSION_COOKIE_NAME)
+                .map(|c| c.value().to_owned());
+
+            let data = match &old_token {
+                Some(token) => store.load(token).await.unwrap_or_default(),
+                None => Map::new(),
+            };
+
+            let shared = Arc::new(SharedSession {
+                data: Mutex::new(data),
+                dirty: Mutex::new(false),
+                renew: Mutex::new(false),
+            });
+            req.extensions_mut().insert(shared.clone());
+
+            let mut res = inner.call(req).await?;
+
+            if *shared.dirty.lock().unwrap() {
+                let data = shared.data.lock().unwrap().clone();
+                let renew = *shared.renew.lock().unwrap();
+                match store.save(&data, old_token.as_deref(), renew).await {
+                    Ok(token) => {
+                        if let Some(old_token) = &old_token {
+                            if old_token != &token {
+                                store.destroy(old_token).await;
+                            }
+                        }
+
+                        let mut cookie = Cookie::new(SESSION_COOKIE_NAME, token);
+                        CookieDefaults {
+                            path: Some("/".into()),
+                            same_site: Some(SameSite::Lax),
+                            secure: Some(true),
+                            http_only: Some(true),
+                            ..Default::default()
+                        }
+                        .apply(&mut cookie);
+
+                        let mut jar = cookie::CookieJar::new();
+                        jar.private_mut(&key).add(cookie);
+                        set_cookies(jar, res.headers_mut());
+                    }
+                    Err(err) => {
+                        res = (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response();
+                    }
+                }
+            }
+
+            Ok(res)
+        })
+    }
+}
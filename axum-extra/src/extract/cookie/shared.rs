@@ -0,0 +1,288 @@
+use super::{cookies_from_request, Cookie};
+#[cfg(any(feature = "cookie-private", feature = "cookie-signed"))]
+use super::Key;
+use axum::{
+    extract::FromRequestParts,
+    response::{IntoResponse, Response},
+};
+use http::{header::SET_COOKIE, request::Parts, HeaderMap, Request, StatusCode};
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// A shared, mutable handle onto the request's cookie jar.
+///
+/// Unlike [`CookieJar`](super::CookieJar), a `Cookies` does not need to be returned from the
+/// handler for its changes to take effect: it's a cheaply cloneable handle around an
+/// `Arc<Mutex<cookie::CookieJar>>`, so `add`/`remove` can be called by shared reference from
+/// handlers, extractors, or middleware that runs after the handler. [`CookiesLayer`] must be
+/// added to the router; it parses the `Cookie` header once per request and, on the way out,
+/// flushes whatever changes were recorded into `Set-Cookie` headers.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get};
+/// use axum_extra::extract::cookie::{Cookie, Cookies, CookiesLayer};
+///
+/// async fn handler(cookies: Cookies) {
+///     cookies.add(Cookie::new("visited", "true"));
+/// }
+///
+/// let app = Router::new()
+///     .route("/", get(handler))
+///     .layer(CookiesLayer::new());
+/// # let _: axum::Router = app;
+/// ```
+#[derive(Debug, Clone)]
+pub struct Cookies {
+    jar: Arc<Mutex<cookie::CookieJar>>,
+}
+
+impl Cookies {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let mut jar = cookie::CookieJar::new();
+        for cookie in cookies_from_request(headers) {
+            jar.add_original(cookie);
+        }
+        Self {
+            jar: Arc::new(Mutex::new(jar)),
+        }
+    }
+
+    /// Get a cookie from the jar.
+    pub fn get(&self, name: &str) -> Option<Cookie<'static>> {
+        self.jar.lock().unwrap().get(name).cloned()
+    }
+
+    /// Add a cookie to the jar.
+    ///
+    /// The value will automatically be percent-encoded.
+    pub fn add<C: Into<Cookie<'static>>>(&self, cookie: C) {
+        self.jar.lock().unwrap().add(cookie);
+    }
+
+    /// Remove a cookie from the jar.
+    pub fn remove<C: Into<Cookie<'static>>>(&self, cookie: C) {
+        self.jar.lock().unwrap().remove(cookie);
+    }
+
+    /// Add a cookie with the specified prefix to the jar.
+    pub fn add_prefixed<P: cookie::prefix::Prefix>(&self, prefix: P, cookie: Cookie<'static>) {
+        self.jar.lock().unwrap().prefixed_mut(prefix).add(cookie);
+    }
+
+    /// Remove a cookie with the specified prefix from the jar.
+    pub fn remove_prefixed<P, S>(&self, prefix: P, name: S)
+    where
+        P: cookie::prefix::Prefix,
+        S: Into<String>,
+    {
+        self.jar.lock().unwrap().prefixed_mut(prefix).remove(name.into());
+    }
+
+    /// Get a view onto this jar that reads and writes cookies encrypted with `key`.
+    #[cfg(feature = "cookie-private")]
+    pub fn private<'a>(&'a self, key: &'a Key) -> PrivateCookies<'a> {
+        PrivateCookies { cookies: self, key }
+    }
+
+    /// Get a view onto this jar that reads and writes cookies signed (but not encrypted) with
+    /// `key`.
+    #[cfg(feature = "cookie-signed")]
+    pub fn signed<'a>(&'a self, key: &'a Key) -> SignedCookies<'a> {
+        SignedCookies { cookies: self, key }
+    }
+}
+
+impl<S> FromRequestParts<S> for Cookies
+where
+    S: Send + Sync,
+{
+    type Rejection = CookiesLayerMissing;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Cookies>()
+            .cloned()
+            .ok_or(CookiesLayerMissing)
+    }
+}
+
+/// Rejection used if [`Cookies`] is extracted without [`CookiesLayer`] having been added to the
+/// router.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct CookiesLayerMissing;
+
+impl fmt::Display for CookiesLayerMissing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`Cookies` extractor used without adding `CookiesLayer` to the router"
+        )
+    }
+}
+
+impl std::error::Error for CookiesLayerMissing {}
+
+impl IntoResponse for CookiesLayerMissing {
+    fn into_response(self) -> Response {
+        (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()).into_response()
+    }
+}
+
+/// A view onto a shared [`Cookies`] jar that reads and writes cookies encrypted with a [`Key`].
+///
+/// See [`Cookies::private`].
+#[cfg(feature = "cookie-private")]
+pub struct PrivateCookies<'a> {
+    cookies: &'a Cookies,
+    key: &'a Key,
+}
+
+#[cfg(feature = "cookie-private")]
+impl PrivateCookies<'_> {
+    /// Get a cookie from the jar, decrypting it if present.
+    pub fn get(&self, name: &str) -> Option<Cookie<'static>> {
+        self.cookies.jar.lock().unwrap().private(self.key).get(name)
+    }
+
+    /// Add an encrypted cookie to the jar.
+    pub fn add<C: Into<Cookie<'static>>>(&self, cookie: C) {
+        self.cookies
+            .jar
+            .lock()
+            .unwrap()
+            .private_mut(self.key)
+            .add(cookie);
+    }
+
+    /// Remove a cookie from the jar.
+    pub fn remove<C: Into<Cookie<'static>>>(&self, cookie: C) {
+        self.cookies
+            .jar
+            .lock()
+            .unwrap()
+            .private_mut(self.key)
+            .remove(cookie);
+    }
+}
+
+/// A view onto a shared [`Cookies`] jar that reads and writes cookies signed (but not encrypted)
+/// with a [`Key`].
+///
+/// See [`Cookies::signed`].
+#[cfg(feature = "cookie-signed")]
+pub struct SignedCookies<'a> {
+    cookies: &'a Cookies,
+    key: &'a Key,
+}
+
+#[cfg(feature = "cookie-signed")]
+impl SignedCookies<'_> {
+    /// Get a cookie from the jar, verifying its signature if present.
+    pub fn get(&self, name: &str) -> Option<Cookie<'static>> {
+        self.cookies.jar.lock().unwrap().signed(self.key).get(name)
+    }
+
+    /// Add a signed cookie to the jar.
+    pub fn add<C: Into<Cookie<'static>>>(&self, cookie: C) {
+        self.cookies
+            .jar
+            .lock()
+            .unwrap()
+            .signed_mut(self.key)
+            .add(cookie);
+    }
+
+    /// Remove a cookie from the jar.
+    pub fn remove<C: Into<Cookie<'static>>>(&self, cookie: C) {
+        self.cookies
+            .jar
+            .lock()
+            .unwrap()
+            .signed_mut(self.key)
+            .remove(cookie);
+    }
+}
+
+/// A [`tower::Layer`] that installs a shared [`Cookies`] jar on every request and flushes any
+/// accumulated changes into `Set-Cookie` headers on the response.
+///
+/// Must be added to the router for the [`Cookies`] extractor to work; see [`Cookies`] for an
+/// example.
+///
+/// [`tower::Layer`]: https://docs.rs/tower/latest/tower/trait.Layer.html
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CookiesLayer;
+
+impl CookiesLayer {
+    /// Create a new `CookiesLayer`.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// An alias for [`CookiesLayer`], for discoverability under the name used by similar jar-manager
+/// middleware in other frameworks (e.g. poem's `CookieJarManager`).
+///
+/// [`Cookies`] is the shared, mutable jar it installs; [`Cookies::private`] and
+/// [`Cookies::signed`] are the immediate-effect, key-scoped views that play the role of a mutable
+/// `PrivateCookieJar` / `SignedCookieJar` without needing to be returned from the handler.
+pub type CookieManagerLayer = CookiesLayer;
+
+impl<S> Layer<S> for CookiesLayer {
+    type Service = CookiesService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CookiesService { inner }
+    }
+}
+
+/// The [`Service`] produced by [`CookiesLayer`]. See that type for details.
+#[derive(Debug, Clone, Copy)]
+pub struct CookiesService<S> {
+    inner: S,
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for CookiesService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        let cookies = Cookies::from_headers(req.headers());
+        req.extensions_mut().insert(cookies.clone());
+
+        let mut inner = self.inner.clone();
+        Box::pin(async move {
+            let mut res = inner.call(req).await?;
+
+            let jar = cookies.jar.lock().unwrap();
+            for cookie in jar.delta() {
+                if let Ok(header_value) = cookie.encoded().to_string().parse() {
+                    res.headers_mut().append(SET_COOKIE, header_value);
+                }
+            }
+
+            Ok(res)
+        })
+    }
+}
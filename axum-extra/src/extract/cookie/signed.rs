@@ -0,0 +1,346 @@
+use super::{cookies_from_request, set_cookies, Cookie, CookieDefaults, Duration, Key};
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    response::{IntoResponse, IntoResponseParts, Response, ResponseParts},
+};
+use cookie::SignedJar;
+use http::{request::Parts, HeaderMap};
+use std::{convert::Infallible, fmt, marker::PhantomData};
+
+/// Extractor that grabs signed cookies from the request and manages the jar.
+///
+/// All cookies will be signed with a [`Key`], making it impossible for a client to forge the
+/// contents or signature of a cookie. Unlike [`PrivateCookieJar`](super::PrivateCookieJar), the
+/// value of a signed cookie is still readable in plaintext by the client; use
+/// [`PrivateCookieJar`](super::PrivateCookieJar) instead if the data needs confidentiality as
+/// well as integrity.
+///
+/// Note that methods like [`SignedCookieJar::add`], [`SignedCookieJar::remove`], etc updates the
+/// [`SignedCookieJar`] and returns it. This value _must_ be returned from the handler as part of
+/// the response for the changes to be propagated.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{
+///     Router,
+///     routing::{post, get},
+///     extract::FromRef,
+///     response::{IntoResponse, Redirect},
+/// };
+/// use axum_extra::extract::cookie::{SignedCookieJar, Cookie, Key};
+///
+/// async fn set_csrf_token(jar: SignedCookieJar) -> (SignedCookieJar, Redirect) {
+///     let updated_jar = jar.add(Cookie::new("csrf_token", "readable-but-tamper-evident"));
+///     (updated_jar, Redirect::to("/get"))
+/// }
+///
+/// async fn get_csrf_token(jar: SignedCookieJar) {
+///     if let Some(cookie) = jar.get("csrf_token") {
+///         // ...
+///     }
+/// }
+///
+/// #[derive(Clone)]
+/// struct AppState {
+///     key: Key,
+/// }
+///
+/// impl FromRef<AppState> for Key {
+///     fn from_ref(state: &AppState) -> Self {
+///         state.key.clone()
+///     }
+/// }
+///
+/// let state = AppState { key: Key::generate() };
+/// let app = Router::new()
+///     .route("/set", post(set_csrf_token))
+///     .route("/get", get(get_csrf_token))
+///     .with_state(state);
+/// # let _: axum::Router = app;
+/// ```
+pub struct SignedCookieJar<K = Key> {
+    jar: cookie::CookieJar,
+    key: Key,
+    defaults: CookieDefaults,
+    _marker: PhantomData<K>,
+}
+
+impl<K> fmt::Debug for SignedCookieJar<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignedCookieJar")
+            .field("jar", &self.jar)
+            .field("key", &"REDACTED")
+            .finish()
+    }
+}
+
+impl<S, K> FromRequestParts<S> for SignedCookieJar<K>
+where
+    S: Send + Sync,
+    K: FromRef<S> + Into<Key>,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let key = K::from_ref(state).into();
+        let SignedCookieJar {
+            jar,
+            key,
+            defaults,
+            _marker: _,
+        } = SignedCookieJar::from_headers(&parts.headers, key);
+        Ok(SignedCookieJar {
+            jar,
+            key,
+            defaults,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl SignedCookieJar {
+    /// Create a new `SignedCookieJar` from a map of request headers.
+    ///
+    /// The cookies in `headers` whose signature validates under `key` will be added to the jar.
+    ///
+    /// This is intended to be used in middleware and other places where it might be difficult to
+    /// run extractors. Normally you should create `SignedCookieJar`s through [`FromRequestParts`].
+    ///
+    /// [`FromRequestParts`]: axum::extract::FromRequestParts
+    pub fn from_headers(headers: &HeaderMap, key: Key) -> Self {
+        let mut jar = cookie::CookieJar::new();
+
+        for cookie in cookies_from_request(headers) {
+            if let Some(plaintext) = jar.signed(&key).verify(cookie) {
+                jar.signed_mut(&key).add_original(plaintext);
+            }
+        }
+
+        Self {
+            jar,
+            key,
+            defaults: CookieDefaults::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Create a new empty `SignedCookieJar`.
+    ///
+    /// This is intended to be used in middleware and other places where it might be difficult to
+    /// run extractors. Normally you should create `SignedCookieJar`s through [`FromRequestParts`].
+    ///
+    /// [`FromRequestParts`]: axum::extract::FromRequestParts
+    pub fn new(key: Key) -> Self {
+        Self {
+            jar: Default::default(),
+            key,
+            defaults: CookieDefaults::default(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K> SignedCookieJar<K> {
+    /// Get a cookie from the jar.
+    ///
+    /// If the cookie exists and its signature is valid, then it is returned in plaintext.
+    pub fn get(&self, name: &str) -> Option<Cookie<'static>> {
+        self.signed_jar().get(name)
+    }
+
+    /// Remove a cookie from the jar.
+    #[must_use]
+    pub fn remove<C: Into<Cookie<'static>>>(mut self, cookie: C) -> Self {
+        self.signed_jar_mut().remove(cookie);
+        self
+    }
+
+    /// Add a signed cookie to the jar.
+    ///
+    /// The value will automatically be percent-encoded.
+    #[must_use]
+    #[allow(clippy::should_implement_trait)]
+    pub fn add<C: Into<Cookie<'static>>>(mut self, cookie: C) -> Self {
+        let mut cookie = cookie.into();
+        self.defaults.apply(&mut cookie);
+        self.signed_jar_mut().add(cookie);
+        self
+    }
+
+    /// Set default cookie attributes that are applied to every cookie added to this jar (via
+    /// [`SignedCookieJar::add`] or [`SignedCookieJar::add_prefixed`]) that doesn't already set
+    /// that attribute explicitly.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum_extra::extract::cookie::{CookieDefaults, SignedCookieJar, SameSite};
+    ///
+    /// async fn handle(jar: SignedCookieJar) -> SignedCookieJar {
+    ///     jar.with_defaults(CookieDefaults {
+    ///         path: Some("/".into()),
+    ///         same_site: Some(SameSite::Strict),
+    ///         secure: Some(true),
+    ///         ..Default::default()
+    ///     })
+    /// }
+    /// ```
+    #[must_use]
+    pub fn with_defaults(mut self, defaults: CookieDefaults) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    /// Verifies the signature of `cookie`, returning the plaintext version if valid or `None`
+    /// otherwise.
+    pub fn verify(&self, cookie: Cookie<'static>) -> Option<Cookie<'static>> {
+        self.signed_jar().verify(cookie)
+    }
+
+    /// Get an iterator over all cookies in the jar.
+    ///
+    /// Only cookies with a valid signature are yielded by the iterator.
+    pub fn iter(&self) -> impl Iterator<Item = Cookie<'static>> + '_ {
+        SignedCookieJarIter {
+            jar: self,
+            iter: self.jar.iter(),
+        }
+    }
+
+    fn signed_jar(&self) -> SignedJar<&'_ cookie::CookieJar> {
+        self.jar.signed(&self.key)
+    }
+
+    fn signed_jar_mut(&mut self) -> SignedJar<&'_ mut cookie::CookieJar> {
+        self.jar.signed_mut(&self.key)
+    }
+
+    /// Add a signed cookie with the specified prefix to the jar.
+    ///
+    /// The cookie's value will be signed using the jar's key, and the prefix is prepended to the
+    /// cookie's name. Only the name changes: attributes the prefix requires (e.g., `Secure`,
+    /// `Path=/` for `__Host-`) are not set by this method.
+    #[must_use]
+    pub fn add_prefixed<P: cookie::prefix::Prefix>(
+        self,
+        _prefix: P,
+        cookie: Cookie<'static>,
+    ) -> Self {
+        let mut jar = self.jar;
+        jar.remove(Cookie::new(cookie.name().to_owned(), ""));
+
+        let prefixed_name = format!("{}{}", P::PREFIX, cookie.name());
+        let mut new_cookie = cookie;
+        self.defaults.apply(&mut new_cookie);
+        new_cookie.set_name(prefixed_name);
+        jar.signed_mut(&self.key).add(new_cookie);
+
+        Self {
+            jar,
+            key: self.key,
+            defaults: self.defaults,
+            _marker: self._marker,
+        }
+    }
+
+    /// Get a signed cookie with the specified prefix from the jar.
+    ///
+    /// If the cookie exists and its signature is valid, it is returned with its prefixed name
+    /// and plaintext value.
+    pub fn get_prefixed<P: cookie::prefix::Prefix>(
+        &self,
+        _prefix: P,
+        name: &str,
+    ) -> Option<Cookie<'static>> {
+        let prefixed_name = format!("{}{name}", P::PREFIX);
+        self.jar
+            .get(&prefixed_name)
+            .and_then(|c| self.verify(c.clone()))
+    }
+
+    /// Remove a signed cookie with the specified prefix from the jar.
+    #[must_use]
+    pub fn remove_prefixed<P, S>(mut self, prefix: P, name: S) -> Self
+    where
+        P: cookie::prefix::Prefix,
+        S: Into<String>,
+    {
+        let mut prefixed_jar = self.jar.prefixed_mut(prefix);
+        prefixed_jar.remove(name.into());
+        self
+    }
+
+    /// Remove a cookie from the jar without recording a removal delta.
+    ///
+    /// Unlike [`SignedCookieJar::remove`], this doesn't queue a `Set-Cookie: ...; Max-Age=0`
+    /// header: the cookie is simply dropped from this jar's own state. Useful for middleware
+    /// that wants to rewrite cookies before a handler ever sees them, without telling the
+    /// browser to delete anything.
+    #[must_use]
+    pub fn force_remove<C: Into<Cookie<'static>>>(mut self, cookie: C) -> Self {
+        self.jar.force_remove(cookie);
+        self
+    }
+
+    /// Queue a `Set-Cookie` that expires `name` in the browser (`Max-Age=0`), regardless of
+    /// whether this jar currently holds a cookie by that name.
+    ///
+    /// Uses the jar's default `Path` (see [`SignedCookieJar::with_defaults`]) so the expiration
+    /// lands even in browsers that match `Set-Cookie` deletions against the original cookie's
+    /// path. The expiration cookie carries no data, so it's written unsigned rather than being
+    /// signed.
+    #[must_use]
+    pub fn expire(mut self, name: impl Into<String>) -> Self {
+        let mut cookie = Cookie::new(name.into(), "");
+        self.defaults.apply(&mut cookie);
+        cookie.set_max_age(Duration::ZERO);
+        self.jar.add(cookie);
+        self
+    }
+}
+
+impl<K> IntoResponseParts for SignedCookieJar<K> {
+    type Error = Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        set_cookies(self.jar, res.headers_mut());
+        Ok(res)
+    }
+}
+
+impl<K> IntoResponse for SignedCookieJar<K> {
+    fn into_response(self) -> Response {
+        (self, ()).into_response()
+    }
+}
+
+struct SignedCookieJarIter<'a, K> {
+    jar: &'a SignedCookieJar<K>,
+    iter: cookie::Iter<'a>,
+}
+
+impl<K> Iterator for SignedCookieJarIter<'_, K> {
+    type Item = Cookie<'static>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let cookie = self.iter.next()?;
+
+            if let Some(cookie) = self.jar.get(cookie.name()) {
+                return Some(cookie);
+            }
+        }
+    }
+}
+
+impl<K> Clone for SignedCookieJar<K> {
+    fn clone(&self) -> Self {
+        Self {
+            jar: self.jar.clone(),
+            key: self.key.clone(),
+            defaults: self.defaults.clone(),
+            _marker: self._marker,
+        }
+    }
+}